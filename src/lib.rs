@@ -1,5 +1,7 @@
 #![no_std]
 
+use core::cmp::Ordering;
+
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 #[inline]
 fn optimizer_hide(mut value: u8) -> u8 {
@@ -50,12 +52,43 @@ fn constant_time_ne(a: &[u8], b: &[u8]) -> u8 {
     let a = &a[..len];
     let b = &b[..len];
 
+    // Compare the common prefix one `usize` word at a time instead of one
+    // byte at a time; this is a large speedup for big buffers (MAC/hash
+    // streams, file digests). The number of chunks and the tail length
+    // depend only on `len`, never on the data, so this stays constant
+    // time for equal-length inputs.
+    const WORD: usize = core::mem::size_of::<usize>();
+    let chunks = len / WORD;
+    let chunked_len = chunks * WORD;
+
+    let mut word_tmp: usize = 0;
+    for i in 0..chunks {
+        let offset = i * WORD;
+        // SAFETY: `offset` ranges over `0..chunks * WORD`, which is within
+        // the bounds of `a` and `b` since `chunks = len / WORD`. The loads
+        // are unaligned because the slices aren't guaranteed to be aligned
+        // to `usize`.
+        unsafe {
+            let a_word = (a.as_ptr().add(offset) as *const usize).read_unaligned();
+            let b_word = (b.as_ptr().add(offset) as *const usize).read_unaligned();
+            word_tmp |= a_word ^ b_word;
+        }
+    }
+
+    // Fold the wide accumulator down to a single byte.
     let mut tmp = 0;
-    for i in 0..len {
+    for byte in word_tmp.to_ne_bytes() {
+        tmp |= byte;
+    }
+
+    // Handle the `len % WORD` tail one byte at a time.
+    for i in chunked_len..len {
         tmp |= a[i] ^ b[i];
     }
 
-    // The compare with 0 must happen outside this function.
+    // The compare with 0 must happen outside this function, and the
+    // reduction above must pass through the barrier too so no early exit
+    // is introduced.
     optimizer_hide(tmp)
 }
 
@@ -79,26 +112,42 @@ pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     a.len() == b.len() && constant_time_ne(a, b) == 0
 }
 
-// Fixed-size variants for the most common sizes.
+// Fixed-size variants for the most common sizes, built on top of a single
+// const-generic comparison so that callers with sizes we don't enumerate
+// (20, 24, 48 bytes, ...) still get a branch-free compare whose loop bound
+// is a compile-time constant.
 
-macro_rules! constant_time_ne_n {
-    ($ne:ident, $n:expr) => {
-        #[inline]
-        fn $ne(a: &[u8; $n], b: &[u8; $n]) -> u8 {
-            let mut tmp = 0;
-            for i in 0..$n {
-                tmp |= a[i] ^ b[i];
-            }
+#[inline]
+fn constant_time_ne_n<const N: usize>(a: &[u8; N], b: &[u8; N]) -> u8 {
+    let mut tmp = 0;
+    for i in 0..N {
+        tmp |= a[i] ^ b[i];
+    }
 
-            // The compare with 0 must happen outside this function.
-            optimizer_hide(tmp)
-        }
-    };
+    // The compare with 0 must happen outside this function.
+    optimizer_hide(tmp)
 }
 
-constant_time_ne_n!(constant_time_ne_16, 16);
-constant_time_ne_n!(constant_time_ne_32, 32);
-constant_time_ne_n!(constant_time_ne_64, 64);
+/// Compares two byte strings of the same, compile-time-known length in
+/// constant time.
+///
+/// Unlike [`constant_time_eq`], the length is part of the type, so the
+/// comparison loop has a compile-time-constant bound and no runtime length
+/// check is emitted, whatever the size. Whether the loop is unrolled is
+/// left to the optimizer.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::constant_time_eq_n;
+///
+/// assert!(constant_time_eq_n(&[3; 20], &[3; 20]));
+/// assert!(!constant_time_eq_n(&[3; 20], &[7; 20]));
+/// ```
+#[inline]
+pub fn constant_time_eq_n<const N: usize>(a: &[u8; N], b: &[u8; N]) -> bool {
+    constant_time_ne_n(a, b) == 0
+}
 
 /// Compares two 128-bit byte strings in constant time.
 ///
@@ -112,7 +161,7 @@ constant_time_ne_n!(constant_time_ne_64, 64);
 /// ```
 #[inline]
 pub fn constant_time_eq_16(a: &[u8; 16], b: &[u8; 16]) -> bool {
-    constant_time_ne_16(a, b) == 0
+    constant_time_eq_n(a, b)
 }
 
 /// Compares two 256-bit byte strings in constant time.
@@ -127,7 +176,7 @@ pub fn constant_time_eq_16(a: &[u8; 16], b: &[u8; 16]) -> bool {
 /// ```
 #[inline]
 pub fn constant_time_eq_32(a: &[u8; 32], b: &[u8; 32]) -> bool {
-    constant_time_ne_32(a, b) == 0
+    constant_time_eq_n(a, b)
 }
 
 /// Compares two 512-bit byte strings in constant time.
@@ -142,5 +191,350 @@ pub fn constant_time_eq_32(a: &[u8; 32], b: &[u8; 32]) -> bool {
 /// ```
 #[inline]
 pub fn constant_time_eq_64(a: &[u8; 64], b: &[u8; 64]) -> bool {
-    constant_time_ne_64(a, b) == 0
+    constant_time_eq_n(a, b)
+}
+
+// Generic comparison over slices of integer types, for callers who work
+// with arrays of words (e.g. big-integer limbs) instead of bytes.
+
+macro_rules! optimizer_hide_width {
+    ($name:ident, $ty:ty) => {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        #[allow(asm_sub_register)]
+        #[inline]
+        fn $name(mut value: $ty) -> $ty {
+            // SAFETY: the input value is passed unchanged to the output, the inline assembly does nothing.
+            unsafe {
+                core::arch::asm!("/* {0} */", inout(reg) value, options(pure, nomem, nostack, preserves_flags));
+                value
+            }
+        }
+
+        #[cfg(any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv32",
+            target_arch = "riscv64"
+        ))]
+        #[allow(asm_sub_register)]
+        #[inline]
+        fn $name(mut value: $ty) -> $ty {
+            // SAFETY: the input value is passed unchanged to the output, the inline assembly does nothing.
+            unsafe {
+                core::arch::asm!("/* {0} */", inout(reg) value, options(pure, nomem, nostack, preserves_flags));
+                value
+            }
+        }
+
+        #[cfg(not(any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv32",
+            target_arch = "riscv64"
+        )))]
+        #[inline(never)] // This function is non-inline to prevent the optimizer from looking inside it.
+        fn $name(value: $ty) -> $ty {
+            // SAFETY: the result of casting a reference to a pointer is valid; the type is Copy.
+            unsafe { core::ptr::read_volatile(&value) }
+        }
+    };
+}
+
+optimizer_hide_width!(optimizer_hide_u16, u16);
+optimizer_hide_width!(optimizer_hide_u32, u32);
+
+// `u64` needs its own cfg split: the `reg` register class only supports up
+// to a 32-bit GPR on 32-bit architectures (`x86`, `arm`, `riscv32`), so
+// `optimizer_hide_width!` would fail to compile a `u64` hide there. Only
+// route through `asm!` on architectures with a native 64-bit GPR, and fall
+// back to the generic `read_volatile` barrier everywhere else.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "riscv64"))]
+#[inline]
+fn optimizer_hide_u64(mut value: u64) -> u64 {
+    // SAFETY: the input value is passed unchanged to the output, the inline assembly does nothing.
+    unsafe {
+        core::arch::asm!("/* {0} */", inout(reg) value, options(pure, nomem, nostack, preserves_flags));
+        value
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "riscv64")))]
+#[inline(never)] // This function is non-inline to prevent the optimizer from looking inside it.
+fn optimizer_hide_u64(value: u64) -> u64 {
+    // SAFETY: the result of casting a reference to a pointer is valid; the type is Copy.
+    unsafe { core::ptr::read_volatile(&value) }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A plain integer type whose bit pattern can be XOR-folded and compared
+/// in constant time.
+///
+/// This trait is sealed: it is implemented for `u8`, `u16`, `u32`, `u64`,
+/// `i8`, `i16`, `i32`, `i64` and `usize`, and cannot be implemented for
+/// any other type outside of this crate.
+pub trait ConstantTimeEqElement: sealed::Sealed + Copy {
+    #[doc(hidden)]
+    const ZERO: Self;
+    #[doc(hidden)]
+    fn ct_xor(self, other: Self) -> Self;
+    #[doc(hidden)]
+    fn ct_or(self, other: Self) -> Self;
+    #[doc(hidden)]
+    fn ct_hide_is_nonzero(self) -> bool;
+}
+
+macro_rules! impl_constant_time_eq_element {
+    ($ty:ty as $unsigned:ty, $hide:expr) => {
+        impl sealed::Sealed for $ty {}
+        impl ConstantTimeEqElement for $ty {
+            const ZERO: Self = 0;
+
+            #[inline]
+            fn ct_xor(self, other: Self) -> Self {
+                self ^ other
+            }
+
+            #[inline]
+            fn ct_or(self, other: Self) -> Self {
+                self | other
+            }
+
+            #[inline]
+            fn ct_hide_is_nonzero(self) -> bool {
+                $hide(self as $unsigned) != 0
+            }
+        }
+    };
+}
+
+impl_constant_time_eq_element!(u8 as u8, optimizer_hide);
+impl_constant_time_eq_element!(i8 as u8, optimizer_hide);
+impl_constant_time_eq_element!(u16 as u16, optimizer_hide_u16);
+impl_constant_time_eq_element!(i16 as u16, optimizer_hide_u16);
+impl_constant_time_eq_element!(u32 as u32, optimizer_hide_u32);
+impl_constant_time_eq_element!(i32 as u32, optimizer_hide_u32);
+impl_constant_time_eq_element!(u64 as u64, optimizer_hide_u64);
+impl_constant_time_eq_element!(i64 as u64, optimizer_hide_u64);
+
+#[cfg(target_pointer_width = "16")]
+impl_constant_time_eq_element!(usize as u16, optimizer_hide_u16);
+#[cfg(target_pointer_width = "32")]
+impl_constant_time_eq_element!(usize as u32, optimizer_hide_u32);
+#[cfg(target_pointer_width = "64")]
+impl_constant_time_eq_element!(usize as u64, optimizer_hide_u64);
+
+/// Extension trait for comparing slices of integer types in constant time.
+///
+/// This mirrors [`constant_time_eq`]/[`constant_time_ne`] for `[T]` where
+/// `T` is any of the integer types listed on [`ConstantTimeEqElement`],
+/// so that crypto code working with arrays of words (e.g. limbs of a big
+/// integer, or `[u32; 8]` hash state) doesn't have to transmute to bytes
+/// first.
+///
+/// This trait is deliberately named and shaped differently from
+/// `subtle::ConstantTimeEq` (which returns a `subtle::Choice` rather than
+/// `bool`), so that depending on both crates and calling this trait's
+/// methods doesn't run into "multiple applicable items in scope" errors.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::ConstantTimeEqSlice;
+///
+/// let a: [u32; 4] = [1, 2, 3, 4];
+/// let b: [u32; 4] = [1, 2, 3, 4];
+/// let c: [u32; 4] = [1, 2, 3, 5];
+///
+/// assert!(a[..].constant_time_eq(&b[..]));
+/// assert!(a[..].constant_time_ne(&c[..]));
+/// ```
+pub trait ConstantTimeEqSlice {
+    /// Compares `self` and `other` for equality in constant time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `other` don't have the same length, just like
+    /// [`constant_time_ne`].
+    fn constant_time_eq(&self, other: &Self) -> bool;
+
+    /// Compares `self` and `other` for inequality in constant time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` and `other` don't have the same length.
+    fn constant_time_ne(&self, other: &Self) -> bool {
+        !self.constant_time_eq(other)
+    }
+}
+
+impl<T: ConstantTimeEqElement> ConstantTimeEqSlice for [T] {
+    fn constant_time_eq(&self, other: &Self) -> bool {
+        assert!(self.len() == other.len());
+
+        // These useless slices make the optimizer elide the bounds checks.
+        // See the comment in clone_from_slice() added on Rust commit 6a7bc47.
+        let len = self.len();
+        let a = &self[..len];
+        let b = &other[..len];
+
+        let mut tmp = T::ZERO;
+        for i in 0..len {
+            tmp = tmp.ct_or(a[i].ct_xor(b[i]));
+        }
+
+        // The accumulator passes through the barrier for its width before
+        // it is compared with zero.
+        !tmp.ct_hide_is_nonzero()
+    }
+}
+
+// Constant-time selection and conditional copy.
+
+/// Selects `a` or `b` in constant time, without branching on `choice`.
+///
+/// Only bit 0 of `choice` is examined: returns `a` if it's set, `b`
+/// otherwise. The other 7 bits are ignored, so e.g. `choice = 3` behaves
+/// like `choice = 1`, not like `choice = 0`.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::constant_time_select;
+///
+/// assert_eq!(constant_time_select(1, 0xaa, 0xbb), 0xaa);
+/// assert_eq!(constant_time_select(0, 0xaa, 0xbb), 0xbb);
+/// ```
+#[inline]
+pub fn constant_time_select(choice: u8, a: u8, b: u8) -> u8 {
+    let mask = optimizer_hide(0u8.wrapping_sub(choice & 1));
+    (a & mask) | (b & !mask)
+}
+
+/// Copies `src` into `dst` only if bit 0 of `choice` is set, in constant time.
+///
+/// Only bit 0 of `choice` is examined: `dst` is overwritten if it's set,
+/// left unchanged otherwise. The other 7 bits are ignored, so e.g.
+/// `choice = 3` behaves like `choice = 1`, not like `choice = 0`. Whether
+/// or not the copy happens, this runs in constant time with respect to
+/// `choice`.
+///
+/// # Panics
+///
+/// Panics if `dst` and `src` don't have the same length.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::constant_time_copy;
+///
+/// let mut dst = [0u8; 4];
+/// constant_time_copy(1, &mut dst, &[1, 2, 3, 4]);
+/// assert_eq!(dst, [1, 2, 3, 4]);
+///
+/// constant_time_copy(0, &mut dst, &[9, 9, 9, 9]);
+/// assert_eq!(dst, [1, 2, 3, 4]);
+/// ```
+pub fn constant_time_copy(choice: u8, dst: &mut [u8], src: &[u8]) {
+    assert!(dst.len() == src.len());
+
+    let mask = optimizer_hide(0u8.wrapping_sub(choice & 1));
+
+    // These useless slices make the optimizer elide the bounds checks.
+    // See the comment in clone_from_slice() added on Rust commit 6a7bc47.
+    let len = dst.len();
+    let dst = &mut dst[..len];
+    let src = &src[..len];
+    for i in 0..len {
+        dst[i] = (src[i] & mask) | (dst[i] & !mask);
+    }
+}
+
+// Constant-time ordering comparison.
+
+/// Compares two equal-length, big-endian byte strings as unsigned
+/// integers in constant time.
+///
+/// This scans from the most significant byte to the least significant
+/// one, but every byte is processed regardless of where the strings
+/// first differ, so the result doesn't leak the magnitude of `a` or `b`
+/// through timing. Useful for range checks on secret scalars (e.g. "is
+/// this key less than the group order") where [`PartialOrd`] would
+/// short-circuit at the first differing byte.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` don't have the same length.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::constant_time_cmp;
+/// use core::cmp::Ordering;
+///
+/// assert_eq!(constant_time_cmp(&[1, 2, 3], &[1, 2, 3]), Ordering::Equal);
+/// assert_eq!(constant_time_cmp(&[1, 2, 3], &[1, 2, 4]), Ordering::Less);
+/// assert_eq!(constant_time_cmp(&[1, 2, 4], &[1, 2, 3]), Ordering::Greater);
+/// ```
+pub fn constant_time_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    assert!(a.len() == b.len());
+
+    // These useless slices make the optimizer elide the bounds checks.
+    // See the comment in clone_from_slice() added on Rust commit 6a7bc47.
+    let len = a.len();
+    let a = &a[..len];
+    let b = &b[..len];
+
+    let mut lt: u8 = 0;
+    let mut gt: u8 = 0;
+    for i in 0..len {
+        let ai = a[i] as i16;
+        let bi = b[i] as i16;
+
+        // Sign-extract without branching: the top bit of an arithmetic
+        // right shift is 1 exactly when the subtraction went negative.
+        let a_lt_b = (((ai - bi) >> 8) & 1) as u8;
+        let a_gt_b = (((bi - ai) >> 8) & 1) as u8;
+
+        // The first differing byte (scanning MSB -> LSB) locks `lt`/`gt`;
+        // every byte after that is masked out and can't change the result.
+        lt |= a_lt_b & !gt;
+        gt |= a_gt_b & !lt;
+    }
+
+    let lt = optimizer_hide(lt) != 0;
+    let gt = optimizer_hide(gt) != 0;
+
+    // The branch on the folded result must happen outside the loop above.
+    if lt {
+        Ordering::Less
+    } else if gt {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Compares two equal-length, big-endian byte strings as unsigned
+/// integers in constant time, returning whether `a` is less than `b`.
+///
+/// See [`constant_time_cmp`] for the constant-time guarantees and panic
+/// behavior.
+///
+/// # Examples
+///
+/// ```
+/// use constant_time_eq::constant_time_lt;
+///
+/// assert!(constant_time_lt(&[1, 2, 3], &[1, 2, 4]));
+/// assert!(!constant_time_lt(&[1, 2, 4], &[1, 2, 3]));
+/// assert!(!constant_time_lt(&[1, 2, 3], &[1, 2, 3]));
+/// ```
+#[inline]
+pub fn constant_time_lt(a: &[u8], b: &[u8]) -> bool {
+    constant_time_cmp(a, b) == Ordering::Less
 }